@@ -0,0 +1,235 @@
+//! Neuroevolution: a small feed-forward net that plays the game, and a
+//! genetic-algorithm trainer that evolves its weights headlessly.
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+use crate::data::LevelData;
+use crate::game::{GameState, PlayerAction};
+
+const INPUT_SIZE: usize = 4;
+const HIDDEN_SIZE: usize = 6;
+const OUTPUT_SIZE: usize = 3;
+const WEIGHT_COUNT: usize =
+    INPUT_SIZE * HIDDEN_SIZE + HIDDEN_SIZE + HIDDEN_SIZE * OUTPUT_SIZE + OUTPUT_SIZE;
+
+/// A fixed-topology feed-forward net: 4 inputs, one 6-neuron tanh hidden
+/// layer, 3 outputs whose argmax picks `Left`/`Right`/`None`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Net {
+    weights: Vec<f32>,
+}
+
+impl Net {
+    pub fn random(rng: &mut StdRng) -> Self {
+        let weights = (0..WEIGHT_COUNT).map(|_| rng.gen_range(-1.0..1.0)).collect();
+        Net { weights }
+    }
+
+    pub fn weights(&self) -> &[f32] {
+        &self.weights
+    }
+
+    /// Loads weights written by `Net::save`, e.g. the net `Trainer::evolve`
+    /// returns, as saved by `--train`.
+    pub fn load(path: &str) -> Self {
+        let contents = std::fs::read_to_string(path)
+            .unwrap_or_else(|err| panic!("failed to read weights {path}: {err}"));
+        json5::from_str(&contents).unwrap_or_else(|err| panic!("failed to parse weights {path}: {err}"))
+    }
+
+    pub fn save(&self, path: &str) {
+        match json5::to_string(self) {
+            Ok(contents) => {
+                if let Err(err) = std::fs::write(path, contents) {
+                    eprintln!("ai: failed to write {path}: {err}");
+                }
+            }
+            Err(err) => eprintln!("ai: failed to serialize weights: {err}"),
+        }
+    }
+
+    fn forward(&self, inputs: [f32; INPUT_SIZE]) -> [f32; OUTPUT_SIZE] {
+        let mut offset = 0;
+        let mut hidden = [0.0f32; HIDDEN_SIZE];
+        for neuron in hidden.iter_mut() {
+            let mut sum: f32 = (0..INPUT_SIZE).map(|i| inputs[i] * self.weights[offset + i]).sum();
+            offset += INPUT_SIZE;
+            sum += self.weights[offset]; // bias
+            offset += 1;
+            *neuron = sum.tanh();
+        }
+
+        let mut outputs = [0.0f32; OUTPUT_SIZE];
+        for out in outputs.iter_mut() {
+            let mut sum: f32 = (0..HIDDEN_SIZE).map(|h| hidden[h] * self.weights[offset + h]).sum();
+            offset += HIDDEN_SIZE;
+            sum += self.weights[offset]; // bias
+            offset += 1;
+            *out = sum;
+        }
+
+        outputs
+    }
+
+    /// Reads the game's current state and picks the action with the
+    /// highest output.
+    pub fn decide(&self, game: &GameState) -> PlayerAction {
+        let (obstacle_dx, obstacle_dy) = game.nearest_obstacle_offset_norm();
+        let inputs = [
+            game.player_x_norm(),
+            obstacle_dx,
+            obstacle_dy,
+            game.current_dir_encoded(),
+        ];
+
+        let outputs = self.forward(inputs);
+        let best = outputs
+            .iter()
+            .enumerate()
+            .max_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap())
+            .map(|(i, _)| i)
+            .unwrap();
+
+        match best {
+            0 => PlayerAction::Left,
+            1 => PlayerAction::Right,
+            _ => PlayerAction::None,
+        }
+    }
+
+    fn crossover(a: &Net, b: &Net, rng: &mut StdRng) -> Net {
+        let weights = a
+            .weights
+            .iter()
+            .zip(b.weights.iter())
+            .map(|(&wa, &wb)| if rng.gen_bool(0.5) { wa } else { wb })
+            .collect();
+        Net { weights }
+    }
+
+    fn mutate(&mut self, rng: &mut StdRng, rate: f32, std_dev: f32) {
+        for weight in &mut self.weights {
+            if rng.gen::<f32>() < rate {
+                *weight += gaussian_sample(rng, std_dev);
+            }
+        }
+    }
+}
+
+/// Samples `N(0, std_dev)` via the Box-Muller transform, using only the
+/// uniform sampling `rand` already gives us.
+fn gaussian_sample(rng: &mut StdRng, std_dev: f32) -> f32 {
+    let u1: f32 = rng.gen_range(f32::EPSILON..1.0);
+    let u2: f32 = rng.gen_range(0.0..1.0);
+    let radius = (-2.0 * u1.ln()).sqrt();
+    radius * (std::f32::consts::TAU * u2).cos() * std_dev
+}
+
+/// Evolves a population of `Net`s against a headless `GameState` using a
+/// genetic algorithm: elitism, tournament selection, uniform crossover, and
+/// Gaussian mutation.
+pub struct Trainer {
+    rng: StdRng,
+}
+
+impl Trainer {
+    const POPULATION_SIZE: usize = 50;
+    const ELITE_FRACTION: f32 = 0.1;
+    const TOURNAMENT_SIZE: usize = 4;
+    const MUTATION_RATE: f32 = 0.1;
+    const MUTATION_STD_DEV: f32 = 0.2;
+    const MAX_TICKS: u32 = 1000;
+
+    pub fn new(seed: u64) -> Self {
+        Trainer {
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    /// Runs `generations` rounds of evolution and returns the fittest net
+    /// seen in the final generation.
+    pub fn evolve(&mut self, generations: u32) -> Net {
+        let level = LevelData::load();
+        let mut population: Vec<Net> = (0..Self::POPULATION_SIZE)
+            .map(|_| Net::random(&mut self.rng))
+            .collect();
+        let mut next_generation: Vec<Net> = Vec::with_capacity(Self::POPULATION_SIZE);
+
+        let elite_count = ((Self::POPULATION_SIZE as f32) * Self::ELITE_FRACTION).ceil() as usize;
+        let elite_count = elite_count.max(1);
+
+        let mut fitnesses = vec![0.0; Self::POPULATION_SIZE];
+
+        for generation in 0..generations {
+            // All nets in a generation face the same obstacle layout, so
+            // fitness differences reflect the net, not the layout.
+            let seed = self.rng.gen();
+            for (net, fitness) in population.iter().zip(fitnesses.iter_mut()) {
+                *fitness = Self::evaluate(net, &level, seed, Self::MAX_TICKS);
+            }
+
+            let mut ranked: Vec<usize> = (0..population.len()).collect();
+            ranked.sort_by(|&a, &b| fitnesses[b].partial_cmp(&fitnesses[a]).unwrap());
+
+            eprintln!(
+                "ai: generation {generation}: best fitness {:.1}",
+                fitnesses[ranked[0]]
+            );
+
+            next_generation.clear();
+            for &i in ranked.iter().take(elite_count) {
+                next_generation.push(population[i].clone());
+            }
+            while next_generation.len() < Self::POPULATION_SIZE {
+                let parent_a = Self::tournament_select(&mut self.rng, &population, &fitnesses);
+                let parent_b = Self::tournament_select(&mut self.rng, &population, &fitnesses);
+                let mut child = Net::crossover(parent_a, parent_b, &mut self.rng);
+                child.mutate(&mut self.rng, Self::MUTATION_RATE, Self::MUTATION_STD_DEV);
+                next_generation.push(child);
+            }
+
+            std::mem::swap(&mut population, &mut next_generation);
+        }
+
+        let seed = self.rng.gen();
+        for (net, fitness) in population.iter().zip(fitnesses.iter_mut()) {
+            *fitness = Self::evaluate(net, &level, seed, Self::MAX_TICKS);
+        }
+        let best_index = (0..population.len())
+            .max_by(|&a, &b| fitnesses[a].partial_cmp(&fitnesses[b]).unwrap())
+            .unwrap();
+        population[best_index].clone()
+    }
+
+    /// Runs a headless game (no ggez draw calls) for up to `max_ticks`,
+    /// using the exact same obstacle-spawn logic as the interactive game.
+    /// Takes an already-loaded `level` so the caller can evaluate an entire
+    /// population without re-reading `level.json5` from disk each time.
+    fn evaluate(net: &Net, level: &LevelData, seed: u64, max_ticks: u32) -> f32 {
+        let mut game = GameState::with_seed_and_level(seed, level.clone());
+        let mut ticks_survived = 0u32;
+
+        for _ in 0..max_ticks {
+            if game.is_dead() {
+                break;
+            }
+            game.drive(net.decide(&game));
+            game.tick();
+            ticks_survived += 1;
+        }
+
+        ticks_survived as f32 + game.score()
+    }
+
+    fn tournament_select<'a>(rng: &mut StdRng, population: &'a [Net], fitnesses: &[f32]) -> &'a Net {
+        let mut best = rng.gen_range(0..population.len());
+        for _ in 1..Self::TOURNAMENT_SIZE {
+            let challenger = rng.gen_range(0..population.len());
+            if fitnesses[challenger] > fitnesses[best] {
+                best = challenger;
+            }
+        }
+        &population[best]
+    }
+}
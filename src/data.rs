@@ -0,0 +1,53 @@
+//! Level/config data loaded from `level.json5`, with hardcoded defaults as a fallback.
+
+use serde::Deserialize;
+
+use crate::game::{DESIRED_FPS, GRID_SIZE};
+
+const LEVEL_PATH: &str = "level.json5";
+
+#[derive(Clone, Copy, Debug, Deserialize)]
+pub struct ObstacleData {
+    pub pos: [i16; 2],
+}
+
+// `grid_size`/`cell_size` are intentionally not here: `GRID_SIZE` and
+// `GRID_CELL_SIZE` are baked into `SCREEN_SIZE` and the window mode before
+// any level file is read, so accepting them here without wiring them through
+// rendering and bounds checks would silently no-op. Out of scope until that
+// plumbing exists.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct LevelData {
+    pub fps: u32,
+    pub spawn_rate: f32,
+    pub obstacle_color: [f32; 4],
+    pub player_start: [i16; 2],
+    pub obstacles: Vec<ObstacleData>,
+}
+
+impl Default for LevelData {
+    fn default() -> Self {
+        LevelData {
+            fps: DESIRED_FPS,
+            spawn_rate: 0.1,
+            obstacle_color: [0.48, 0.39, 0.93, 1.0],
+            player_start: [GRID_SIZE.0 / 2, GRID_SIZE.1 - 1],
+            obstacles: Vec::new(),
+        }
+    }
+}
+
+impl LevelData {
+    /// Loads `level.json5` from the working directory, falling back to
+    /// `LevelData::default()` when the file is missing or fails to parse.
+    pub fn load() -> Self {
+        match std::fs::read_to_string(LEVEL_PATH) {
+            Ok(contents) => json5::from_str(&contents).unwrap_or_else(|err| {
+                eprintln!("data: failed to parse {LEVEL_PATH}: {err}, using defaults");
+                LevelData::default()
+            }),
+            Err(_) => LevelData::default(),
+        }
+    }
+}
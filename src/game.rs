@@ -1,20 +1,22 @@
-use ggez::{
-    event, graphics,
-    input::keyboard::{KeyCode, KeyInput},
-    Context, GameResult, glam::Vec2
-};
+use ggez::{graphics, Context, glam::Vec2};
 
-use rand::Rng;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
 
-const GRID_SIZE: (i16, i16) = (30, 20);
-const GRID_CELL_SIZE: (i16, i16) = (32, 32);
+use crate::data::LevelData;
+use crate::input::InputAction;
+
+const REPLAY_PATH: &str = "replay.json5";
+
+pub(crate) const GRID_SIZE: (i16, i16) = (30, 20);
+pub(crate) const GRID_CELL_SIZE: (i16, i16) = (32, 32);
 
 pub const SCREEN_SIZE: (f32, f32) = (
     GRID_SIZE.0 as f32 * GRID_CELL_SIZE.0 as f32,
     GRID_SIZE.1 as f32 * GRID_CELL_SIZE.1 as f32,
 );
 
-const DESIRED_FPS: u32 = 8;
+pub(crate) const DESIRED_FPS: u32 = 8;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 struct GridPosition {
@@ -27,14 +29,6 @@ impl GridPosition {
         GridPosition { x, y }
     }
 
-    pub fn random() -> Self {
-        let mut rng = rand::thread_rng();
-        GridPosition {
-            x: rng.gen_range(0..GRID_SIZE.0),
-            y: rng.gen_range(0..GRID_SIZE.1),
-        }
-    }
-
     pub fn new_for_move(pos: GridPosition, dir: Direction) -> Self {
         match dir {
             Direction::Left => GridPosition::new(pos.x - 1, pos.y),
@@ -61,7 +55,7 @@ impl From<(i16, i16)> for GridPosition {
     }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 enum Direction {
     Left,
     Right,
@@ -69,10 +63,10 @@ enum Direction {
 }
 
 impl Direction {
-    pub fn from_keycode(keycode: KeyCode) -> Option<Self> {
-        match keycode {
-            KeyCode::Left => Some(Direction::Left),
-            KeyCode::Right => Some(Direction::Right),
+    pub fn from_action(action: InputAction) -> Option<Self> {
+        match action {
+            InputAction::MoveLeft => Some(Direction::Left),
+            InputAction::MoveRight => Some(Direction::Right),
             _ => None,
         }
     }
@@ -95,25 +89,114 @@ impl Segment {
 
 struct Obstacle {
     pos: GridPosition,
+    color: graphics::Color,
 }
 
 impl Obstacle {
-    pub fn new(pos: GridPosition) -> Self {
-        Obstacle { pos }
+    pub fn new(pos: GridPosition, color: graphics::Color) -> Self {
+        Obstacle { pos, color }
     }
 
     fn draw(&self, canvas: &mut graphics::Canvas) {
-        let color = graphics::Color::new(0.48, 0.39, 0.93, 1.0);
-        
         canvas.draw(
             &graphics::Quad,
             graphics::DrawParam::new()
                 .dest_rect(self.pos.into())
-                .color(color),
+                .color(self.color),
         );
     }
 }
 
+/// Generates a whole starting obstacle field with a cellular-automata smoothing
+/// pass, instead of the single random obstacle the game used to start with.
+pub struct LevelGenerator {
+    rng: StdRng,
+}
+
+impl LevelGenerator {
+    const FILL_PROBABILITY: f32 = 0.45;
+    const SMOOTHING_PASSES: u32 = 5;
+    const SURVIVAL_THRESHOLD: u8 = 5;
+
+    pub fn new(seed: u64) -> Self {
+        LevelGenerator {
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    /// Produces the starting obstacle field, with the player's actual spawn
+    /// cell (`start_col`, `start_row`) always carved clear in the rows
+    /// obstacles are actually drawn from, and colored with the level's
+    /// configured `obstacle_color`.
+    pub fn generate(&mut self, start_col: i16, start_row: i16, color: graphics::Color) -> Vec<Obstacle> {
+        let width = GRID_SIZE.0 as usize;
+        let height = GRID_SIZE.1 as usize;
+
+        let mut grid: Vec<bool> = (0..width * height)
+            .map(|_| self.rng.gen::<f32>() < Self::FILL_PROBABILITY)
+            .collect();
+
+        for _ in 0..Self::SMOOTHING_PASSES {
+            grid = Self::smooth(&grid, width, height);
+        }
+
+        let upper_rows = height / 2;
+        let start_col = (start_col as usize).min(width - 1);
+        // Clamped to `usize` only to handle a negative row; `carve_clear`'s own
+        // bounds check is what actually keeps an out-of-field spawn (the
+        // shipped default, below `upper_rows`) from carving anything.
+        let start_row = start_row.max(0) as usize;
+        Self::carve_clear(&mut grid, width, upper_rows, start_col, start_row);
+
+        (0..upper_rows)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .filter(|&(x, y)| grid[y * width + x])
+            .map(|(x, y)| Obstacle::new(GridPosition::new(x as i16, y as i16), color))
+            .collect()
+    }
+
+    fn smooth(grid: &[bool], width: usize, height: usize) -> Vec<bool> {
+        (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(|(x, y)| Self::solid_neighbors(grid, width, height, x, y) >= Self::SURVIVAL_THRESHOLD)
+            .collect()
+    }
+
+    /// Counts solid cells among the 8 Moore neighbors, treating anything
+    /// out-of-bounds as solid so the edges of the map fill in.
+    fn solid_neighbors(grid: &[bool], width: usize, height: usize, x: usize, y: usize) -> u8 {
+        let mut count = 0;
+        for dy in -1..=1i32 {
+            for dx in -1..=1i32 {
+                if dx == 0 && dy == 0 {
+                    continue;
+                }
+                let nx = x as i32 + dx;
+                let ny = y as i32 + dy;
+                let solid = nx < 0 || ny < 0 || nx >= width as i32 || ny >= height as i32 || {
+                    grid[ny as usize * width + nx as usize]
+                };
+                if solid {
+                    count += 1;
+                }
+            }
+        }
+        count
+    }
+
+    fn carve_clear(grid: &mut [bool], width: usize, height: usize, col: usize, row: usize) {
+        for dy in -1..=1i32 {
+            for dx in -1..=1i32 {
+                let x = col as i32 + dx;
+                let y = row as i32 + dy;
+                if x >= 0 && y >= 0 && (x as usize) < width && (y as usize) < height {
+                    grid[y as usize * width + x as usize] = false;
+                }
+            }
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug)]
 enum PlayerState {
     Alive,
@@ -152,17 +235,15 @@ impl Player {
     }
 
     fn update(&mut self, dir: Direction, obstacles: &[Obstacle]) {
+        if let PlayerState::Dead = self.state {
+            return;
+        }
+
         self.body.update(dir);
         self.dir = dir;
 
         if self.die(obstacles, 1) {
             self.state = PlayerState::Dead;
-
-            use std::process::Command;
-            let _ = Command::new("pause").status();
-
-            self.body.pos = GridPosition::new(GRID_SIZE.0 / 2, GRID_SIZE.1 - 1);
-            self.dir = Direction::None;
         }
     }
 
@@ -179,98 +260,252 @@ impl Player {
 
 }
 
+/// A recorded run: the seed it was played with plus the direction held on
+/// every tick, enough to reproduce the run exactly via `GameState::from_replay`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct Replay {
+    seed: u64,
+    inputs: Vec<(u64, Direction)>,
+}
+
+/// The three choices a player (human, replay, or AI) can make on a given tick.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PlayerAction {
+    Left,
+    Right,
+    None,
+}
+
 pub struct GameState {
     player: Player,
     obstacles: Vec<Obstacle>,
     score: f32,
+    level: LevelData,
+    rng: StdRng,
+    seed: u64,
+    tick_count: u64,
+    recorded_inputs: Vec<(u64, Direction)>,
+    replay_inputs: Option<Vec<(u64, Direction)>>,
 }
 
 impl GameState {
     pub fn new() -> Self {
-        let player = Player::new(GridPosition::new(GRID_SIZE.0 / 2, GRID_SIZE.1 - 1));
-        let obstacles_pos = GridPosition::random();
-        let obstacles = vec![Obstacle::new(obstacles_pos)];
-        GameState { 
+        Self::with_seed(rand::random())
+    }
+
+    /// Re-plays a run recorded by a previous game over, reproducing the
+    /// obstacle field and every input exactly.
+    pub fn from_replay(path: &str) -> Self {
+        let contents = std::fs::read_to_string(path)
+            .unwrap_or_else(|err| panic!("failed to read replay {path}: {err}"));
+        let replay: Replay = json5::from_str(&contents)
+            .unwrap_or_else(|err| panic!("failed to parse replay {path}: {err}"));
+
+        let mut state = Self::with_seed(replay.seed);
+        state.replay_inputs = Some(replay.inputs);
+        state
+    }
+
+    pub(crate) fn with_seed(seed: u64) -> Self {
+        Self::with_seed_and_level(seed, LevelData::load())
+    }
+
+    /// Like `with_seed`, but takes an already-loaded `LevelData` instead of
+    /// reading `level.json5` again. Used by the neuroevolution trainer, which
+    /// otherwise re-reads the level file on every population member of every
+    /// generation.
+    pub(crate) fn with_seed_and_level(seed: u64, level: LevelData) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed);
+
+        let player = Player::new(GridPosition::new(level.player_start[0], level.player_start[1]));
+        let obstacle_color = graphics::Color::new(
+            level.obstacle_color[0],
+            level.obstacle_color[1],
+            level.obstacle_color[2],
+            level.obstacle_color[3],
+        );
+
+        let obstacles = if level.obstacles.is_empty() {
+            LevelGenerator::new(rng.gen()).generate(
+                level.player_start[0],
+                level.player_start[1],
+                obstacle_color,
+            )
+        } else {
+            level
+                .obstacles
+                .iter()
+                .map(|data| Obstacle::new(GridPosition::new(data.pos[0], data.pos[1]), obstacle_color))
+                .collect()
+        };
+
+        GameState {
             player,
             obstacles,
             score: 0.0,
+            level,
+            rng,
+            seed,
+            tick_count: 0,
+            recorded_inputs: Vec::new(),
+            replay_inputs: None,
+        }
+    }
+
+    /// Writes the seed and recorded inputs of this run to `replay.json5` so
+    /// it can be reproduced later via `GameState::from_replay`.
+    pub fn save_replay(&self) {
+        let replay = Replay {
+            seed: self.seed,
+            inputs: self.recorded_inputs.clone(),
+        };
+
+        match json5::to_string(&replay) {
+            Ok(contents) => {
+                if let Err(err) = std::fs::write(REPLAY_PATH, contents) {
+                    eprintln!("game: failed to write {REPLAY_PATH}: {err}");
+                }
+            }
+            Err(err) => eprintln!("game: failed to serialize replay: {err}"),
         }
     }
 
     fn update_obstacle(&mut self) {
+        let obstacle_color = graphics::Color::new(
+            self.level.obstacle_color[0],
+            self.level.obstacle_color[1],
+            self.level.obstacle_color[2],
+            self.level.obstacle_color[3],
+        );
+
         for obstacle in &mut self.obstacles {
             obstacle.pos.y += 1;
         }
 
         self.obstacles.retain(|obstacle| obstacle.pos.y < GRID_SIZE.1);
 
-        if rand::random::<f32>() < 0.1 {
-            let new_obstacle = Obstacle::new(GridPosition::new(rand::thread_rng().gen_range(0..GRID_SIZE.0), 0));
+        if self.rng.gen::<f32>() < self.level.spawn_rate {
+            let new_obstacle = Obstacle::new(
+                GridPosition::new(self.rng.gen_range(0..GRID_SIZE.0), 0),
+                obstacle_color,
+            );
             self.obstacles.push(new_obstacle);
         }
-    }    
-}
+    }
 
-impl event::EventHandler for GameState {
-    fn update(&mut self, ctx: &mut Context) -> GameResult<()> {
-        while ctx.time.check_update_time(DESIRED_FPS) {
-            self.player.update(self.player.dir, &self.obstacles);
-            self.update_obstacle();
+    /// Advances the simulation. The caller (`PlayScene`) is responsible for
+    /// reacting to `is_dead()` becoming true once this returns.
+    pub fn update(&mut self, ctx: &mut Context) {
+        while ctx.time.check_update_time(self.level.fps) {
+            self.tick();
+        }
+    }
 
-            if self.player.body.pos.x < 0 && self.player.dir == Direction::Left {
-                self.player.dir = Direction::Right;
-            }
-            if self.player.body.pos.x >= GRID_SIZE.0 && self.player.dir == Direction::Right {
-                self.player.dir = Direction::Left;
+    /// Runs one simulation tick, independent of ggez's `Context`. Shared by
+    /// the interactive loop (via `update`), input replay, and the headless
+    /// sim the neuroevolution trainer runs, so obstacle spawning and movement
+    /// are identical in every mode.
+    pub fn tick(&mut self) {
+        if let Some(replay_inputs) = &self.replay_inputs {
+            if let Some(&(_, dir)) = replay_inputs.get(self.tick_count as usize) {
+                self.player.dir = dir;
             }
+        }
+        self.recorded_inputs.push((self.tick_count, self.player.dir));
 
-            if let PlayerState::Alive = self.player.state {
-                self.score += 0.1;
-            }
+        self.player.update(self.player.dir, &self.obstacles);
+        self.update_obstacle();
+
+        if self.player.body.pos.x < 0 && self.player.dir == Direction::Left {
+            self.player.dir = Direction::Right;
+        }
+        if self.player.body.pos.x >= GRID_SIZE.0 && self.player.dir == Direction::Right {
+            self.player.dir = Direction::Left;
+        }
+
+        if let PlayerState::Alive = self.player.state {
+            self.score += 0.1;
         }
 
-        Ok(())
+        self.tick_count += 1;
+    }
+
+    /// Sets the player's direction directly, for callers that aren't
+    /// dispatching through `handle_input` (namely the AI driver).
+    pub fn drive(&mut self, action: PlayerAction) {
+        self.player.dir = match action {
+            PlayerAction::Left => Direction::Left,
+            PlayerAction::Right => Direction::Right,
+            PlayerAction::None => Direction::None,
+        };
     }
 
-    fn draw(&mut self, ctx: &mut Context) -> GameResult<()> {
-        // Create a canvas
-        let mut canvas = graphics::Canvas::from_frame(ctx, graphics::Color::BLACK);
+    pub fn fps(&self) -> u32 {
+        self.level.fps
+    }
 
-        self.player.draw(&mut canvas);
-        for obstacle in &self.obstacles {
-            obstacle.draw(&mut canvas);
+    /// Player x position divided by the grid width, for feeding the AI's net:
+    /// `[0, 1)` while the player is on the grid, outside that range once the
+    /// player has stepped past an edge.
+    pub(crate) fn player_x_norm(&self) -> f32 {
+        self.player.body.pos.x as f32 / GRID_SIZE.0 as f32
+    }
+
+    pub(crate) fn current_dir_encoded(&self) -> f32 {
+        match self.player.dir {
+            Direction::Left => -1.0,
+            Direction::Right => 1.0,
+            Direction::None => 0.0,
         }
+    }
 
-        if let PlayerState::Alive = self.player.state {
-            let text = graphics::Text::new(format!("Score: {}", self.score.trunc()));
-            let dest_point = Vec2::new(0.0, 0.0);
-            canvas.draw(
-                &text, 
-                graphics::DrawParam::from(dest_point).color(graphics::Color::WHITE)
-            );
+    /// The nearest obstacle's offset from the player, normalized by grid
+    /// size. Falls back to a "far away" sentinel when there are no
+    /// obstacles, so the net always sees a well-formed input.
+    pub(crate) fn nearest_obstacle_offset_norm(&self) -> (f32, f32) {
+        const FAR_AWAY: f32 = 2.0;
+
+        let player_pos = self.player.body.pos;
+        let nearest = self.obstacles.iter().min_by_key(|obstacle| {
+            let dx = (obstacle.pos.x - player_pos.x) as i32;
+            let dy = (obstacle.pos.y - player_pos.y) as i32;
+            dx * dx + dy * dy
+        });
+
+        match nearest {
+            Some(obstacle) => (
+                (obstacle.pos.x - player_pos.x) as f32 / GRID_SIZE.0 as f32,
+                (obstacle.pos.y - player_pos.y) as f32 / GRID_SIZE.1 as f32,
+            ),
+            None => (FAR_AWAY, FAR_AWAY),
         }
+    }
 
-        if let PlayerState::Dead = self.player.state {
-            let text = graphics::Text::new(format!("Game Over! \n Score: {}", self.score.trunc()));
-            let dest_point = Vec2::new((SCREEN_SIZE.0 / 2.0) - 2.0, SCREEN_SIZE.1 / 2.0);
-            canvas.draw(
-                &text, 
-                graphics::DrawParam::from(dest_point).color(graphics::Color::WHITE)
-            );
+    pub fn draw(&self, canvas: &mut graphics::Canvas) {
+        self.player.draw(canvas);
+        for obstacle in &self.obstacles {
+            obstacle.draw(canvas);
         }
 
-        canvas.finish(ctx)?;
-        
-        Ok(())
+        let text = graphics::Text::new(format!("Score: {}", self.score.trunc()));
+        let dest_point = Vec2::new(0.0, 0.0);
+        canvas.draw(
+            &text,
+            graphics::DrawParam::from(dest_point).color(graphics::Color::WHITE),
+        );
     }
-    
-    fn key_down_event(&mut self, _ctx: &mut Context, input: KeyInput, _repeat: bool) -> GameResult {
-        if let Some(dir) = input.keycode.and_then(Direction::from_keycode) {
-            if dir == Direction::Left || dir == Direction::Right {
-                self.player.dir = dir;
-            }
+
+    pub fn handle_input(&mut self, action: InputAction) {
+        if let Some(dir) = Direction::from_action(action) {
+            self.player.dir = dir;
         }
-        
-        Ok(())
+    }
+
+    pub fn is_dead(&self) -> bool {
+        matches!(self.player.state, PlayerState::Dead)
+    }
+
+    pub fn score(&self) -> f32 {
+        self.score
     }
 }
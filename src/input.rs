@@ -0,0 +1,74 @@
+//! Unifies keyboard and gamepad input behind a single `InputAction` enum so
+//! scenes don't need to know which device drove them.
+
+use ggez::input::{gamepad::gilrs, keyboard::KeyCode};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InputAction {
+    MoveLeft,
+    MoveRight,
+    Confirm,
+    Quit,
+}
+
+impl InputAction {
+    pub fn from_keycode(keycode: KeyCode) -> Option<Self> {
+        match keycode {
+            KeyCode::Left => Some(InputAction::MoveLeft),
+            KeyCode::Right => Some(InputAction::MoveRight),
+            KeyCode::Return => Some(InputAction::Confirm),
+            KeyCode::Escape => Some(InputAction::Quit),
+            _ => None,
+        }
+    }
+
+    pub fn from_button(button: gilrs::Button) -> Option<Self> {
+        match button {
+            gilrs::Button::DPadLeft => Some(InputAction::MoveLeft),
+            gilrs::Button::DPadRight => Some(InputAction::MoveRight),
+            gilrs::Button::South => Some(InputAction::Confirm),
+            gilrs::Button::East => Some(InputAction::Quit),
+            _ => None,
+        }
+    }
+}
+
+/// Tracks gamepad axis state so the D-pad/analog stick can drive
+/// `Player::dir` the same way keyboard left/right does.
+pub struct ControllerManager {
+    last_axis_action: Option<InputAction>,
+}
+
+impl ControllerManager {
+    const AXIS_DEAD_ZONE: f32 = 0.35;
+
+    pub fn new() -> Self {
+        ControllerManager {
+            last_axis_action: None,
+        }
+    }
+
+    pub fn button_down(&self, button: gilrs::Button) -> Option<InputAction> {
+        InputAction::from_button(button)
+    }
+
+    /// Maps the horizontal stick axis to `MoveLeft`/`MoveRight` past a dead
+    /// zone, firing only when the resulting direction changes so holding the
+    /// stick over doesn't spam the same action every poll.
+    pub fn left_stick_x(&mut self, value: f32) -> Option<InputAction> {
+        let action = if value <= -Self::AXIS_DEAD_ZONE {
+            Some(InputAction::MoveLeft)
+        } else if value >= Self::AXIS_DEAD_ZONE {
+            Some(InputAction::MoveRight)
+        } else {
+            None
+        };
+
+        if action == self.last_axis_action {
+            return None;
+        }
+
+        self.last_axis_action = action;
+        action
+    }
+}
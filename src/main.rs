@@ -1,12 +1,52 @@
 use ggez::{event, GameResult};
+mod ai;
+mod data;
 mod game;
+mod input;
+mod scene;
+
+const DEFAULT_WEIGHTS_PATH: &str = "weights.json5";
 
 fn main() -> GameResult {
+    let args: Vec<String> = std::env::args().collect();
+
+    if let Some(generations) = flag_value(&args, "--train") {
+        let generations: u32 = generations.parse().expect("--train expects a generation count");
+        train(generations);
+        return Ok(());
+    }
+
     let cb = ggez::ContextBuilder::new("rusty-scape", "cristoxdxd");
     let (ctx, event_loop) = cb
         .window_setup(ggez::conf::WindowSetup::default().title("rusty-scape"))
         .window_mode(ggez::conf::WindowMode::default().dimensions(game::SCREEN_SIZE.0, game::SCREEN_SIZE.1))
         .build()?;
-    let state = game::GameState::new();
+
+    let initial_scene: Box<dyn scene::Scene> = if let Some(path) = flag_value(&args, "--replay") {
+        Box::new(scene::PlayScene::from_replay(&path))
+    } else if let Some(path) = flag_value(&args, "--ai") {
+        Box::new(scene::PlayScene::with_ai(ai::Net::load(&path)))
+    } else {
+        Box::new(scene::MenuScene::new())
+    };
+
+    let state = scene::SceneStack::new(initial_scene);
     event::run(ctx, event_loop, state)
 }
+
+/// Evolves a net for `generations` rounds and writes the fittest to
+/// `weights.json5`, for `--ai` to pick up afterwards.
+fn train(generations: u32) {
+    let mut trainer = ai::Trainer::new(rand::random());
+    let best = trainer.evolve(generations);
+    best.save(DEFAULT_WEIGHTS_PATH);
+    println!("wrote trained weights to {DEFAULT_WEIGHTS_PATH}");
+}
+
+/// Returns the value following `flag` in the CLI args, e.g. `--ai weights.json5`.
+fn flag_value(args: &[String], flag: &str) -> Option<String> {
+    args.iter()
+        .position(|arg| arg == flag)
+        .and_then(|i| args.get(i + 1))
+        .cloned()
+}
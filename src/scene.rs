@@ -0,0 +1,249 @@
+//! Scene/state machine that replaces driving `GameState` directly as the
+//! top-level `event::EventHandler`, so the game can move between a menu,
+//! active play, and a recoverable game-over screen.
+
+use ggez::{
+    event,
+    graphics,
+    input::gamepad::{gilrs, GamepadId},
+    glam::Vec2, Context, GameResult,
+};
+
+use crate::ai::Net;
+use crate::game::{GameState, SCREEN_SIZE};
+use crate::input::{ControllerManager, InputAction};
+
+/// What a scene wants to happen to the stack after handling an event.
+pub enum Transition {
+    Push(Box<dyn Scene>),
+    Pop,
+    Quit,
+}
+
+pub trait Scene {
+    fn update(&mut self, ctx: &mut Context) -> GameResult<Option<Transition>>;
+    fn draw(&mut self, ctx: &mut Context, canvas: &mut graphics::Canvas) -> GameResult<()>;
+    fn input(&mut self, ctx: &mut Context, action: InputAction) -> GameResult<Option<Transition>>;
+
+    /// Called on the scene that becomes the top of the stack again after a
+    /// `Transition::Pop`, e.g. so `PlayScene` can start a fresh run.
+    fn on_resume(&mut self) {}
+}
+
+/// Owns the stack of scenes and is the actual ggez `EventHandler` root.
+/// Only the top scene is updated and drawn. Keyboard input (via
+/// `InputAction::from_keycode`) and gamepad input (via `ControllerManager`)
+/// are both funneled into `Scene::input`.
+pub struct SceneStack {
+    scenes: Vec<Box<dyn Scene>>,
+    controller: ControllerManager,
+}
+
+impl SceneStack {
+    pub fn new(initial: Box<dyn Scene>) -> Self {
+        SceneStack {
+            scenes: vec![initial],
+            controller: ControllerManager::new(),
+        }
+    }
+
+    fn apply(&mut self, ctx: &mut Context, transition: Option<Transition>) {
+        match transition {
+            Some(Transition::Push(scene)) => self.scenes.push(scene),
+            Some(Transition::Pop) => {
+                self.scenes.pop();
+                if let Some(scene) = self.scenes.last_mut() {
+                    scene.on_resume();
+                }
+            }
+            Some(Transition::Quit) => ctx.request_quit(),
+            None => {}
+        }
+    }
+
+    fn top(&mut self) -> &mut Box<dyn Scene> {
+        self.scenes.last_mut().expect("scene stack is empty")
+    }
+
+    fn dispatch(&mut self, ctx: &mut Context, action: Option<InputAction>) -> GameResult<()> {
+        let Some(action) = action else {
+            return Ok(());
+        };
+
+        let transition = self.top().input(ctx, action)?;
+        self.apply(ctx, transition);
+        Ok(())
+    }
+}
+
+impl event::EventHandler for SceneStack {
+    fn update(&mut self, ctx: &mut Context) -> GameResult<()> {
+        let transition = self.top().update(ctx)?;
+        self.apply(ctx, transition);
+        Ok(())
+    }
+
+    fn draw(&mut self, ctx: &mut Context) -> GameResult<()> {
+        let mut canvas = graphics::Canvas::from_frame(ctx, graphics::Color::BLACK);
+        self.top().draw(ctx, &mut canvas)?;
+        canvas.finish(ctx)?;
+        Ok(())
+    }
+
+    fn key_down_event(&mut self, ctx: &mut Context, input: ggez::input::keyboard::KeyInput, _repeat: bool) -> GameResult {
+        let action = input.keycode.and_then(InputAction::from_keycode);
+        self.dispatch(ctx, action)
+    }
+
+    fn gamepad_button_down_event(&mut self, ctx: &mut Context, btn: gilrs::Button, _id: GamepadId) -> GameResult {
+        let action = self.controller.button_down(btn);
+        self.dispatch(ctx, action)
+    }
+
+    fn gamepad_axis_event(&mut self, ctx: &mut Context, axis: gilrs::Axis, value: f32, _id: GamepadId) -> GameResult {
+        let action = if axis == gilrs::Axis::LeftStickX {
+            self.controller.left_stick_x(value)
+        } else {
+            None
+        };
+        self.dispatch(ctx, action)
+    }
+}
+
+/// The title screen shown on launch.
+pub struct MenuScene;
+
+impl MenuScene {
+    pub fn new() -> Self {
+        MenuScene
+    }
+}
+
+impl Scene for MenuScene {
+    fn update(&mut self, _ctx: &mut Context) -> GameResult<Option<Transition>> {
+        Ok(None)
+    }
+
+    fn draw(&mut self, _ctx: &mut Context, canvas: &mut graphics::Canvas) -> GameResult<()> {
+        let text = graphics::Text::new("rusty-scape\n\nPress Enter to start\nPress Esc to quit");
+        let dest_point = Vec2::new(SCREEN_SIZE.0 / 2.0 - 80.0, SCREEN_SIZE.1 / 2.0 - 20.0);
+        canvas.draw(
+            &text,
+            graphics::DrawParam::from(dest_point).color(graphics::Color::WHITE),
+        );
+        Ok(())
+    }
+
+    fn input(&mut self, _ctx: &mut Context, action: InputAction) -> GameResult<Option<Transition>> {
+        match action {
+            InputAction::Confirm => Ok(Some(Transition::Push(Box::new(PlayScene::new())))),
+            InputAction::Quit => Ok(Some(Transition::Quit)),
+            _ => Ok(None),
+        }
+    }
+}
+
+/// Active gameplay. Wraps `GameState`, which holds the actual simulation.
+/// When `ai` is set, it drives `player.dir` every tick instead of the input
+/// dispatched through `Scene::input`.
+pub struct PlayScene {
+    game: GameState,
+    ai: Option<Net>,
+}
+
+impl PlayScene {
+    pub fn new() -> Self {
+        PlayScene {
+            game: GameState::new(),
+            ai: None,
+        }
+    }
+
+    pub fn from_replay(path: &str) -> Self {
+        PlayScene {
+            game: GameState::from_replay(path),
+            ai: None,
+        }
+    }
+
+    pub fn with_ai(net: Net) -> Self {
+        PlayScene {
+            game: GameState::new(),
+            ai: Some(net),
+        }
+    }
+}
+
+impl Scene for PlayScene {
+    fn update(&mut self, ctx: &mut Context) -> GameResult<Option<Transition>> {
+        if let Some(net) = &self.ai {
+            while ctx.time.check_update_time(self.game.fps()) {
+                self.game.drive(net.decide(&self.game));
+                self.game.tick();
+            }
+        } else {
+            self.game.update(ctx);
+        }
+
+        if self.game.is_dead() {
+            self.game.save_replay();
+            return Ok(Some(Transition::Push(Box::new(GameOverScene::new(self.game.score())))));
+        }
+
+        Ok(None)
+    }
+
+    fn draw(&mut self, _ctx: &mut Context, canvas: &mut graphics::Canvas) -> GameResult<()> {
+        self.game.draw(canvas);
+        Ok(())
+    }
+
+    fn input(&mut self, _ctx: &mut Context, action: InputAction) -> GameResult<Option<Transition>> {
+        if self.ai.is_none() {
+            self.game.handle_input(action);
+        }
+        Ok(None)
+    }
+
+    fn on_resume(&mut self) {
+        self.game = GameState::new();
+    }
+}
+
+/// Shown after death; restart pops back to a fresh `PlayScene`.
+pub struct GameOverScene {
+    score: f32,
+}
+
+impl GameOverScene {
+    pub fn new(score: f32) -> Self {
+        GameOverScene { score }
+    }
+}
+
+impl Scene for GameOverScene {
+    fn update(&mut self, _ctx: &mut Context) -> GameResult<Option<Transition>> {
+        Ok(None)
+    }
+
+    fn draw(&mut self, _ctx: &mut Context, canvas: &mut graphics::Canvas) -> GameResult<()> {
+        let text = graphics::Text::new(format!(
+            "Game Over!\nScore: {}\n\nPress Enter to restart\nPress Esc to quit",
+            self.score.trunc()
+        ));
+        let dest_point = Vec2::new(SCREEN_SIZE.0 / 2.0 - 80.0, SCREEN_SIZE.1 / 2.0 - 20.0);
+        canvas.draw(
+            &text,
+            graphics::DrawParam::from(dest_point).color(graphics::Color::WHITE),
+        );
+        Ok(())
+    }
+
+    fn input(&mut self, _ctx: &mut Context, action: InputAction) -> GameResult<Option<Transition>> {
+        match action {
+            InputAction::Confirm => Ok(Some(Transition::Pop)),
+            InputAction::Quit => Ok(Some(Transition::Quit)),
+            _ => Ok(None),
+        }
+    }
+}